@@ -10,10 +10,14 @@ use std::str::{self, FromStr};
 use std::task::{Context, Poll};
 use std::time::Duration;
 
+use std::collections::HashMap;
+use std::thread;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
 #[derive(Debug, Clone)]
 pub enum RequestMethod {
-	// TODO Support CONNECT - https://curl.se/libcurl/c/CURLOPT_HTTPPROXYTUNNEL.html
-	// Connect,
+	Connect,
 	Delete,
 	Get,
 	Head,
@@ -28,7 +32,7 @@ pub enum RequestMethod {
 impl<'a> From<&'a RequestMethod> for &'a str {
 	fn from(request_method: &'a RequestMethod) -> &'a str {
 		match request_method {
-			// RequestMethod::Connect => "CONNECT",
+			RequestMethod::Connect => "CONNECT",
 			RequestMethod::Delete => "DELETE",
 			RequestMethod::Get => "GET",
 			RequestMethod::Head => "HEAD",
@@ -46,7 +50,7 @@ impl From<String> for RequestMethod {
 	fn from(request_method: String) -> Self {
 		let request_method = request_method.to_uppercase();
 		match request_method.as_str() {
-			// "CONNECT" => Self::Connect,
+			"CONNECT" => Self::Connect,
 			"DELETE" => RequestMethod::Delete,
 			"GET" => RequestMethod::Get,
 			"HEAD" => RequestMethod::Head,
@@ -60,6 +64,16 @@ impl From<String> for RequestMethod {
 	}
 }
 
+/// HTTP version to request from curl via `CURLOPT_HTTP_VERSION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpVersion {
+	Http1_0,
+	Http1_1,
+	Http2,
+	Http2PriorKnowledge,
+	Http3,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
 	pub location: bool,
@@ -71,6 +85,33 @@ pub struct Config {
 	pub url: String,
 	pub verbose: bool,
 	pub max_response_size: Option<usize>,
+	/// Number of requests to fire. Read by [`httpstat_samples`], which aggregates
+	/// the runs into an [`AggregateTiming`]; [`httpstat`] always fires a single request.
+	pub count: usize,
+	/// Maximum number of requests kept in flight at once while sampling.
+	pub concurrency: usize,
+	/// Encodings to offer in the `Accept-Encoding` request header. When set, curl
+	/// is *not* allowed to auto-decode; the raw wire bytes are kept and
+	/// decompressed manually so the compression ratio can be reported.
+	pub accept_encoding: Option<Vec<String>>,
+	/// Preferred HTTP version. `None` leaves curl to negotiate its default.
+	pub http_version: Option<HttpVersion>,
+	/// Proxy URL (e.g. `http://proxy:3128` or `socks5://host:1080`).
+	pub proxy: Option<String>,
+	/// Tunnel through the proxy with `CONNECT` rather than letting it relay the
+	/// request. Required for HTTPS origins behind an HTTP proxy.
+	pub proxy_tunnel: bool,
+	pub proxy_username: Option<String>,
+	pub proxy_password: Option<String>,
+	/// Byte range to request as `(start, end)`. `end` of `None` means open-ended
+	/// (`bytes=start-`). Drives the `Range` header for tail/follow mode.
+	pub range: Option<(u64, Option<u64>)>,
+	/// Total deadline for the whole request, wired to `CURLOPT_TIMEOUT`.
+	pub timeout: Option<Duration>,
+	/// Abort if the transfer stays below this many bytes/sec for `low_speed_time`.
+	pub low_speed_limit: Option<u32>,
+	/// Window over which `low_speed_limit` must be breached before aborting.
+	pub low_speed_time: Option<Duration>,
 }
 
 impl Default for Config {
@@ -85,6 +126,18 @@ impl Default for Config {
 			url: "".into(),
 			verbose: false,
 			max_response_size: None,
+			count: 1,
+			concurrency: 1,
+			accept_encoding: None,
+			http_version: None,
+			proxy: None,
+			proxy_tunnel: false,
+			proxy_username: None,
+			proxy_password: None,
+			range: None,
+			timeout: None,
+			low_speed_limit: None,
+			low_speed_time: None,
 		}
 	}
 }
@@ -143,37 +196,65 @@ impl From<String> for HttpResponseHeader {
 pub struct Timing {
 	pub namelookup: Duration,
 	pub connect: Duration,
+	pub appconnect: Duration,
 	pub pretransfer: Duration,
 	pub starttransfer: Duration,
 	pub total: Duration,
 	pub dns_resolution: Duration,
 	pub tcp_connection: Duration,
+	pub proxy_connect: Duration,
 	pub tls_connection: Duration,
 	pub server_processing: Duration,
 	pub content_transfer: Duration,
 }
 
 impl Timing {
-	pub fn new(handle: &mut Easy2Handle<Collector>) -> Self {
+	pub fn new(handle: &mut Easy2Handle<Collector>, proxy_tunnel: bool) -> Self {
 		let namelookup = handle.namelookup_time().unwrap();
 		let connect = handle.connect_time().unwrap();
+		let appconnect = handle.appconnect_time().unwrap();
 		let pretransfer = handle.pretransfer_time().unwrap();
 		let starttransfer = handle.starttransfer_time().unwrap();
 		let total = handle.total_time().unwrap();
 		let dns_resolution = namelookup;
 		let tcp_connection = connect - namelookup;
-		let tls_connection = pretransfer - connect;
+
+		// When tunnelling, everything between the TCP connect to the proxy and the
+		// origin TLS handshake completing (`appconnect`) is tunnel setup plus origin
+		// TLS. curl exposes no timestamp marking the end of the CONNECT exchange on
+		// its own, so these two are NOT separable: the origin TLS handshake is
+		// reported together with the tunnel in `proxy_connect`, and the CLI's
+		// single-request breakdown folds them into a single "Proxy + TLS" segment
+		// rather than a bogus ~0ms TLS cell. The repeated-sampling summary has no
+		// proxy row; its TLS row shows `tls_connection` alone. For a non-TLS origin
+		// there is no `appconnect`, so the whole pre-transfer interval after the
+		// TCP connect is the tunnel.
+		let (proxy_connect, tls_connection) = if proxy_tunnel {
+			if appconnect > connect {
+				(
+					appconnect - connect,
+					pretransfer.saturating_sub(appconnect),
+				)
+			} else {
+				(pretransfer.saturating_sub(connect), Duration::from_secs(0))
+			}
+		} else {
+			(Duration::from_secs(0), pretransfer - connect)
+		};
+
 		let server_processing = starttransfer - pretransfer;
 		let content_transfer = total - starttransfer;
 
 		Self {
 			namelookup,
 			connect,
+			appconnect,
 			pretransfer,
 			starttransfer,
 			total,
 			dns_resolution,
 			tcp_connection,
+			proxy_connect,
 			tls_connection,
 			server_processing,
 			content_transfer,
@@ -181,6 +262,117 @@ impl Timing {
 	}
 }
 
+/// Distribution of a single timing phase across a batch of samples.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PhaseStats {
+	pub min: Duration,
+	pub mean: Duration,
+	pub median: Duration,
+	pub p95: Duration,
+	pub p99: Duration,
+	pub max: Duration,
+	pub stddev: Duration,
+}
+
+impl PhaseStats {
+	/// Summarise a slice of samples. An empty slice yields all-zero stats so an
+	/// all-failures batch reports cleanly instead of panicking.
+	fn from_samples(samples: &[Duration]) -> Self {
+		if samples.is_empty() {
+			let zero = Duration::from_secs(0);
+			return Self {
+				min: zero,
+				mean: zero,
+				median: zero,
+				p95: zero,
+				p99: zero,
+				max: zero,
+				stddev: zero,
+			};
+		}
+
+		let mut secs: Vec<f64> = samples.iter().map(Duration::as_secs_f64).collect();
+		secs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+		let len = secs.len();
+		let sum: f64 = secs.iter().sum();
+		let mean = sum / len as f64;
+		let variance = secs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / len as f64;
+
+		Self {
+			min: Duration::from_secs_f64(secs[0]),
+			mean: Duration::from_secs_f64(mean),
+			median: Duration::from_secs_f64(percentile(&secs, 50.0)),
+			p95: Duration::from_secs_f64(percentile(&secs, 95.0)),
+			p99: Duration::from_secs_f64(percentile(&secs, 99.0)),
+			max: Duration::from_secs_f64(secs[len - 1]),
+			stddev: Duration::from_secs_f64(variance.sqrt()),
+		}
+	}
+}
+
+/// Percentile over a pre-sorted slice: the element at position
+/// `p / 100 * (len - 1)`, rounded to the nearest index.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+	let len = sorted.len();
+	if len == 1 {
+		return sorted[0];
+	}
+	let rank = (p / 100.0) * (len - 1) as f64;
+	sorted[rank.round() as usize]
+}
+
+/// Per-phase latency distribution gathered from repeated sampling.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AggregateTiming {
+	pub samples: usize,
+	pub failures: usize,
+	pub dns_resolution: PhaseStats,
+	pub tcp_connection: PhaseStats,
+	pub tls_connection: PhaseStats,
+	pub server_processing: PhaseStats,
+	pub content_transfer: PhaseStats,
+	pub total: PhaseStats,
+}
+
+impl AggregateTiming {
+	/// Collect the per-run [`Timing`] samples into a per-phase distribution. Runs
+	/// that errored out never make it into `timings`; their count is carried in
+	/// `failures` so the summary can report them without skewing the percentiles.
+	pub fn from_timings(timings: &[Timing], failures: usize) -> Self {
+		let phase = |f: fn(&Timing) -> Duration| -> PhaseStats {
+			let samples: Vec<Duration> = timings.iter().map(f).collect();
+			PhaseStats::from_samples(&samples)
+		};
+
+		Self {
+			samples: timings.len(),
+			failures,
+			dns_resolution: phase(|t| t.dns_resolution),
+			tcp_connection: phase(|t| t.tcp_connection),
+			tls_connection: phase(|t| t.tls_connection),
+			server_processing: phase(|t| t.server_processing),
+			content_transfer: phase(|t| t.content_transfer),
+			total: phase(|t| t.total),
+		}
+	}
+}
+
+/// What was actually negotiated on the wire, after the transfer completes. Lets
+/// the tool confirm whether HTTP/2 or HTTP/3 really engaged rather than just what
+/// was requested.
+///
+/// The effective HTTP version is read from the status line of the final response
+/// — the version the server actually answered with. The `curl` binding in use
+/// exposes no getinfo for the negotiated TLS protocol/cipher
+/// (`CURLINFO_TLS_SSL_PTR` is not wrapped), so those are not reported here rather
+/// than shipping permanently-empty fields.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Negotiated {
+	/// Effective HTTP version, e.g. `"1.1"` or `"2"`.
+	pub http_version: String,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct StatResult {
 	pub http_version: String,
@@ -189,20 +381,30 @@ pub struct StatResult {
 	pub headers: Vec<Header>,
 	pub timing: Timing,
 	pub body: Vec<u8>,
+	/// Number of bytes received on the wire (after any compression).
+	pub compressed_size: usize,
+	/// Size of `body` after manual decompression. Equals `compressed_size` when
+	/// the response was not compressed.
+	pub decompressed_size: usize,
+	/// The `Content-Encoding` the response was decoded with, if any, trimmed and
+	/// lower-cased. An unknown encoding is still reported even though the bytes
+	/// are left untouched.
+	pub encoding: Option<String>,
+	pub negotiated: Negotiated,
 }
 
 pub struct Collector<'a> {
 	config: &'a Config,
-	headers: &'a mut Vec<u8>,
-	data: &'a mut Vec<u8>,
+	headers: Vec<u8>,
+	data: Vec<u8>,
 }
 
 impl<'a> Collector<'a> {
-	pub fn new(config: &'a Config, data: &'a mut Vec<u8>, headers: &'a mut Vec<u8>) -> Self {
+	pub fn new(config: &'a Config) -> Self {
 		Self {
 			config,
-			data,
-			headers,
+			data: Vec::new(),
+			headers: Vec::new(),
 		}
 	}
 }
@@ -251,11 +453,11 @@ impl<'a> Future for HttpstatFuture<'a> {
 	}
 }
 
-// TODO now make a sync version
-pub async fn httpstat(config: &Config) -> Result<StatResult> {
-	let mut body = Vec::new();
-	let mut headers = Vec::new();
-	let mut handle = Easy2::new(Collector::new(config, &mut body, &mut headers));
+/// Build and configure a single `Easy2` handle from `config`. Shared by the
+/// single-shot [`httpstat`] path and the repeated-sampling [`httpstat_samples`]
+/// path so both speak to curl identically.
+fn configure_handle(config: &Config) -> Result<Easy2<Collector>> {
+	let mut handle = Easy2::new(Collector::new(config));
 
 	handle.url(&config.url)?;
 	handle.show_header(false)?;
@@ -275,6 +477,64 @@ pub async fn httpstat(config: &Config) -> Result<StatResult> {
 		handle.connect_timeout(connect_timeout)?;
 	}
 
+	if let Some(timeout) = config.timeout {
+		handle.timeout(timeout)?;
+	}
+
+	if let Some(low_speed_limit) = config.low_speed_limit {
+		handle.low_speed_limit(low_speed_limit)?;
+	}
+
+	if let Some(low_speed_time) = config.low_speed_time {
+		handle.low_speed_time(low_speed_time)?;
+	}
+
+	if let Some(proxy) = &config.proxy {
+		use curl::easy::ProxyType;
+		handle.proxy(proxy)?;
+		let proxy_type = if proxy.starts_with("socks5h") {
+			ProxyType::Socks5Hostname
+		} else if proxy.starts_with("socks5") {
+			ProxyType::Socks5
+		} else if proxy.starts_with("socks4a") {
+			ProxyType::Socks4a
+		} else if proxy.starts_with("socks4") {
+			ProxyType::Socks4
+		} else {
+			ProxyType::Http
+		};
+		handle.proxy_type(proxy_type)?;
+		if config.proxy_tunnel {
+			handle.http_proxy_tunnel(true)?;
+		}
+		if let Some(username) = &config.proxy_username {
+			handle.proxy_username(username)?;
+		}
+		if let Some(password) = &config.proxy_password {
+			handle.proxy_password(password)?;
+		}
+	}
+
+	if let Some(version) = config.http_version {
+		use curl::easy::HttpVersion as CurlHttpVersion;
+		let curl_version = match version {
+			HttpVersion::Http1_0 => CurlHttpVersion::V10,
+			HttpVersion::Http1_1 => CurlHttpVersion::V11,
+			HttpVersion::Http2 => CurlHttpVersion::V2,
+			HttpVersion::Http2PriorKnowledge => CurlHttpVersion::V2PriorKnowledge,
+			HttpVersion::Http3 => CurlHttpVersion::V3,
+		};
+		handle.http_version(curl_version)?;
+	}
+
+	if let Some((start, end)) = config.range {
+		let range = match end {
+			Some(end) => format!("{}-{}", start, end),
+			None => format!("{}-", start),
+		};
+		handle.range(&range)?;
+	}
+
 	let data_len = config.data.as_ref().map(|data| data.len() as u64);
 
 	let request_method = &config.request_method;
@@ -300,24 +560,132 @@ pub async fn httpstat(config: &Config) -> Result<StatResult> {
 		handle.post_field_size(data_len.unwrap())?;
 	}
 
-	if let Some(config_headers) = &config.headers {
+	if config.headers.is_some() || config.accept_encoding.is_some() {
 		let mut headers = List::new();
-		for header in config_headers {
-			headers.append(&header.to_string())?;
+		if let Some(config_headers) = &config.headers {
+			for header in config_headers {
+				headers.append(&header.to_string())?;
+			}
+		}
+		// Offer the encodings ourselves rather than going through
+		// `Easy2::accept_encoding`, which would make curl transparently decode the
+		// body and hide the wire size we want to measure.
+		if let Some(encodings) = &config.accept_encoding {
+			if !encodings.is_empty() {
+				headers.append(&format!("Accept-Encoding: {}", encodings.join(", ")))?;
+			}
 		}
 		handle.http_headers(headers)?;
 	}
 
+	Ok(handle)
+}
+
+/// Parse the raw header bytes captured by a [`Collector`] into a status line and
+/// a list of [`Header`]s.
+fn parse_headers(raw: &[u8]) -> Result<(Option<HttpResponseHeader>, Vec<Header>)> {
+	let header_lines = str::from_utf8(raw)?.lines();
+
+	let mut http_response_header: Option<HttpResponseHeader> = None;
+	let mut headers: Vec<Header> = Vec::new();
+
+	let header_iter = header_lines
+		.map(|line| line.replace("\r", "").replace("\n", ""))
+		.filter(|line| !line.is_empty());
+
+	for line in header_iter {
+		if line.to_uppercase().starts_with("HTTP/") {
+			http_response_header = Some(HttpResponseHeader::from(line.to_string()));
+		} else if let Ok(header) = Header::from_str(&line) {
+			headers.push(header);
+		}
+	}
+
+	Ok((http_response_header, headers))
+}
+
+/// A single turn of the `Multi` driver: performs once and resolves with the
+/// number of transfers still running. Used by [`httpstat_samples`] so it can
+/// refill and drain completion messages between performs.
+pub struct HttpstatStep<'a>(&'a Multi);
+
+impl<'a> Future for HttpstatStep<'a> {
+	type Output = Result<usize>;
+
+	fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+		match self.0.perform() {
+			Ok(running) => {
+				context.waker().wake_by_ref();
+				Poll::Ready(Ok(running as usize))
+			}
+			Err(error) => Poll::Ready(Err(error.into())),
+		}
+	}
+}
+
+/// Decode the raw wire bytes according to `Content-Encoding`.
+///
+/// Returns the decoded body together with the encoding that was applied. An
+/// absent, empty or `identity` encoding passes the bytes through untouched. An
+/// unrecognised encoding is reported (trimmed and lower-cased) but its bytes are
+/// left as received rather than failing the whole request.
+fn decode_body(encoding: Option<&str>, raw: &[u8]) -> Result<(Vec<u8>, Option<String>)> {
+	// An empty body (e.g. a HEAD response or a 304) has no compression frame to
+	// decode; feeding it to a decoder would error on the missing header.
+	if raw.is_empty() {
+		return Ok((Vec::new(), None));
+	}
+
+	// Decode best-effort: a decoder that errors out — a truncated or malformed
+	// frame, or one curl would have tolerated — must not discard
+	// the status/headers/timing we already have, so fall back to the raw bytes.
+	let inflate = |mut reader: &mut dyn Read| -> Option<Vec<u8>> {
+		let mut decoded = Vec::new();
+		reader.read_to_end(&mut decoded).ok().map(|_| decoded)
+	};
+
+	let encoding = encoding.map(|e| e.trim().to_lowercase());
+	match encoding.as_deref() {
+		None | Some("") | Some("identity") => Ok((raw.to_vec(), None)),
+		Some("gzip") => {
+			let decoded = inflate(&mut GzDecoder::new(raw)).unwrap_or_else(|| raw.to_vec());
+			Ok((decoded, Some("gzip".into())))
+		}
+		Some("deflate") => {
+			// `deflate` may be zlib-wrapped (RFC 1950) or raw DEFLATE (RFC 1951);
+			// curl accepts both, so try the wrapped form first and fall back to raw.
+			let decoded = inflate(&mut ZlibDecoder::new(raw))
+				.or_else(|| inflate(&mut DeflateDecoder::new(raw)))
+				.unwrap_or_else(|| raw.to_vec());
+			Ok((decoded, Some("deflate".into())))
+		}
+		Some("br") => {
+			let decoded =
+				inflate(&mut brotli::Decompressor::new(raw, 4096)).unwrap_or_else(|| raw.to_vec());
+			Ok((decoded, Some("br".into())))
+		}
+		Some(other) => Ok((raw.to_vec(), Some(other.into()))),
+	}
+}
+
+// TODO now make a sync version
+pub async fn httpstat(config: &Config) -> Result<StatResult> {
+	let handle = configure_handle(config)?;
+
 	let multi = Multi::new();
 	let mut handle = multi.add2(handle)?;
 	HttpstatFuture(&multi).await?;
 
 	// hmmm
 	let mut transfer_result: Result<()> = Ok(());
+	let mut timed_out = false;
 	multi.messages(|m| {
 		if let Ok(()) = transfer_result {
 			if let Some(Err(error)) = m.result_for2(&handle) {
-				if error.is_write_error() {
+				if error.is_operation_timedout() {
+					timed_out = true;
+					transfer_result = Err(error.into());
+				} else if error.is_write_error() {
 					transfer_result = Err(anyhow!("Maximum response size reached"));
 				} else {
 					transfer_result = Err(error.into());
@@ -325,28 +693,54 @@ pub async fn httpstat(config: &Config) -> Result<StatResult> {
 			}
 		}
 	});
+
+	// On a timeout, surface the partial timing gathered up to the abort so users
+	// can still see where the time went before the deadline hit. curl's
+	// cumulative timestamps are reported as-is (a stage never reached reads as
+	// zero) since the per-phase deltas of `Timing::new` would underflow.
+	if timed_out {
+		let total = handle.total_time()?;
+		let deadline = config.timeout.unwrap_or(total);
+		return Err(anyhow!(
+			"Request timed out after {:?} (reached {:?} into the transfer; namelookup {:?}, connect {:?}, appconnect {:?}, pretransfer {:?}, starttransfer {:?})",
+			deadline,
+			total,
+			handle.namelookup_time()?,
+			handle.connect_time()?,
+			handle.appconnect_time()?,
+			handle.pretransfer_time()?,
+			handle.starttransfer_time()?
+		));
+	}
+
 	transfer_result?;
 
-	let timing = Timing::new(&mut handle);
-	// Force handler to drop so we can access the body references held by the collector
-	drop(handle);
+	let timing = Timing::new(&mut handle, config.proxy_tunnel);
 
-	let header_lines = str::from_utf8(&headers[..])?.lines();
+	// Reclaim the handle so we can take ownership of the buffers the collector filled.
+	let mut easy = multi.remove2(handle)?;
+	let body = std::mem::take(&mut easy.get_mut().data);
+	let raw_headers = std::mem::take(&mut easy.get_mut().headers);
 
-	let mut http_response_header: Option<HttpResponseHeader> = None;
-	let mut headers: Vec<Header> = Vec::new();
+	let (http_response_header, headers) = parse_headers(&raw_headers)?;
 
-	let header_iter = header_lines
-		.map(|line| line.replace("\r", "").replace("\n", ""))
-		.filter(|line| !line.is_empty());
+	let content_encoding = headers
+		.iter()
+		.find(|h| h.name.eq_ignore_ascii_case("content-encoding"))
+		.map(|h| h.value.clone());
 
-	for line in header_iter {
-		if line.to_uppercase().starts_with("HTTP/") {
-			http_response_header = Some(HttpResponseHeader::from(line.to_string()));
-		} else if let Ok(header) = Header::from_str(&line) {
-			headers.push(header);
-		}
-	}
+	let compressed_size = body.len();
+	let (body, encoding) = decode_body(content_encoding.as_deref(), &body)?;
+	let decompressed_size = body.len();
+
+	// The effective protocol is reported by curl on the status line of the final
+	// response; the binding exposes no getinfo for the negotiated TLS
+	// protocol/cipher, so those are not reported.
+	let negotiated = Negotiated {
+		http_version: http_response_header
+			.as_ref()
+			.map_or_else(|| "Unknown".into(), |h| h.http_version.clone()),
+	};
 
 	Ok(StatResult {
 		http_version: http_response_header
@@ -361,5 +755,133 @@ pub async fn httpstat(config: &Config) -> Result<StatResult> {
 		headers,
 		body,
 		timing,
+		compressed_size,
+		decompressed_size,
+		encoding,
+		negotiated,
 	})
 }
+
+/// Fire `config.count` requests, keeping up to `config.concurrency` in flight at
+/// once, and aggregate the per-run [`Timing`] phases into an [`AggregateTiming`].
+///
+/// Completion order under `Multi` is arbitrary, so samples are gathered as each
+/// handle finishes and never assumed to be ordered. Runs that curl reports as
+/// failed are excluded from the percentiles and counted separately.
+pub async fn httpstat_samples(config: &Config) -> Result<AggregateTiming> {
+	let count = config.count.max(1);
+	let concurrency = config.concurrency.max(1).min(count);
+
+	let multi = Multi::new();
+	let mut handles: HashMap<usize, Easy2Handle<Collector>> = HashMap::new();
+	let mut timings: Vec<Timing> = Vec::new();
+	let mut failures = 0usize;
+	let mut started = 0usize;
+	let mut next_token = 0usize;
+
+	while timings.len() + failures < count {
+		// Refill the in-flight set up to the concurrency ceiling.
+		while started < count && handles.len() < concurrency {
+			let mut handle = multi.add2(configure_handle(config)?)?;
+			handle.set_token(next_token)?;
+			handles.insert(next_token, handle);
+			next_token += 1;
+			started += 1;
+		}
+
+		// Drive the transfers one step. `perform` returns the number still running;
+		// anything that dropped off has a completion message waiting.
+		HttpstatStep(&multi).await?;
+
+		let mut completed: Vec<(usize, Result<()>)> = Vec::new();
+		multi.messages(|m| {
+			let token = m.token().unwrap();
+			if let Some(handle) = handles.get(&token) {
+				if let Some(result) = m.result_for2(handle) {
+					completed.push((token, result.map_err(Into::into)));
+				}
+			}
+		});
+
+		for (token, result) in completed {
+			let handle = handles.remove(&token).unwrap();
+			match result {
+				Ok(()) => {
+					let mut handle = handle;
+					timings.push(Timing::new(&mut handle, config.proxy_tunnel));
+					multi.remove2(handle)?;
+				}
+				Err(_) => {
+					failures += 1;
+					multi.remove2(handle)?;
+				}
+			}
+		}
+	}
+
+	Ok(AggregateTiming::from_timings(&timings, failures))
+}
+
+/// Tail an HTTP-served resource by repeatedly issuing open-ended `Range`
+/// requests from a running byte offset.
+///
+/// Starting from `config.range` (or offset 0), each poll requests
+/// `bytes=<offset>-`, hands any freshly received bytes to `sink` together with
+/// that poll's [`Timing`], advances the offset and then sleeps for `interval`.
+///
+/// Three server behaviours are handled:
+/// * `206 Partial Content` — the body is the new tail; advance the offset by its
+///   length.
+/// * `416 Range Not Satisfiable` — nothing past the offset yet; keep the offset
+///   and try again after the interval.
+/// * `200 OK` with no `Content-Range` — the server ignored the range and sent
+///   the whole body, so diff from the stored offset and emit only the remainder.
+///
+/// The loop runs until an error occurs or the process is interrupted.
+pub async fn httpstat_follow<F>(config: &Config, interval: Duration, mut sink: F) -> Result<()>
+where
+	F: FnMut(&[u8], &Timing) -> Result<()>,
+{
+	let mut offset = config.range.map_or(0, |(start, _)| start);
+
+	loop {
+		let mut config = config.clone();
+		config.range = Some((offset, None));
+		let result = httpstat(&config).await?;
+
+		let has_content_range = result
+			.headers
+			.iter()
+			.any(|h| h.name.eq_ignore_ascii_case("content-range"));
+
+		match result.response_code {
+			// Range not satisfiable: nothing new past the offset yet.
+			416 => {}
+			// Server honoured the range — the body is the tail we asked for.
+			206 => {
+				if !result.body.is_empty() {
+					sink(&result.body, &result.timing)?;
+					offset += result.body.len() as u64;
+				}
+			}
+			// A plain 200 without `Content-Range` means the range was ignored and
+			// the full body was returned; emit only what we haven't seen.
+			_ if !has_content_range => {
+				let body_len = result.body.len() as u64;
+				if body_len > offset {
+					sink(&result.body[offset as usize..], &result.timing)?;
+					offset = body_len;
+				}
+			}
+			// 200 carrying a `Content-Range` (rare) is treated like a partial tail.
+			_ => {
+				if !result.body.is_empty() {
+					sink(&result.body, &result.timing)?;
+					offset += result.body.len() as u64;
+				}
+			}
+		}
+
+		thread::sleep(interval);
+	}
+}
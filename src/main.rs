@@ -3,11 +3,14 @@ use futures::executor::block_on;
 use nanoid::nanoid;
 use std::env;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{self, Write};
 use std::time::Duration;
 use structopt::StructOpt;
 
-use httpstat::{httpstat, Config, Header};
+use httpstat::{
+	httpstat, httpstat_follow, httpstat_samples, AggregateTiming, Config, Header, HttpVersion,
+	PhaseStats,
+};
 
 #[derive(Debug, Clone, StructOpt)]
 #[structopt()]
@@ -65,6 +68,62 @@ struct Opt {
 	#[structopt(name = "bytes", short = "s", long = "max-response-size")]
 	max_response_size: Option<usize>,
 
+	/// Number of requests to fire, aggregating the timing phases statistically
+	#[structopt(name = "count", short = "n", long = "count", default_value = "1")]
+	count: usize,
+
+	/// Number of requests to keep in flight at once when sampling
+	#[structopt(name = "concurrency", long = "concurrency", default_value = "1")]
+	concurrency: usize,
+
+	/// Request a compressed response (gzip, br, deflate) and report the ratio
+	#[structopt(long = "compressed")]
+	compressed: bool,
+
+	/// Use HTTP/2
+	#[structopt(long = "http2")]
+	http2: bool,
+
+	/// Use HTTP/3
+	#[structopt(long = "http3")]
+	http3: bool,
+
+	/// Use the specified proxy
+	#[structopt(name = "proxy url", short = "x", long = "proxy")]
+	proxy: Option<String>,
+
+	/// Tunnel through the proxy with CONNECT
+	#[structopt(long = "proxy-tunnel")]
+	proxy_tunnel: bool,
+
+	/// Proxy credentials as <user[:password]>
+	#[structopt(name = "proxy user", long = "proxy-user")]
+	proxy_user: Option<String>,
+
+	/// Follow the resource, emitting newly appended bytes via Range requests
+	#[structopt(long = "follow", visible_alias = "tail")]
+	follow: bool,
+
+	/// Start byte offset for Range requests (used as the initial offset when following)
+	#[structopt(name = "offset", short = "r", long = "range")]
+	range: Option<u64>,
+
+	/// Poll interval in milliseconds when following
+	#[structopt(name = "interval", long = "interval", default_value = "1000")]
+	interval: u64,
+
+	/// Maximum time allowed for the whole request, in milliseconds
+	#[structopt(name = "max millis", long = "max-time")]
+	timeout: Option<u64>,
+
+	/// Abort below this many bytes/sec (paired with --speed-time)
+	#[structopt(name = "bytes per sec", long = "speed-limit")]
+	low_speed_limit: Option<u32>,
+
+	/// Seconds the transfer may stay below --speed-limit before aborting
+	#[structopt(name = "seconds", long = "speed-time")]
+	low_speed_time: Option<u64>,
+
 	/// URL to work with
 	url: String,
 }
@@ -84,6 +143,14 @@ fn get_upload_data(data: Option<String>) -> Result<Option<String>> {
 
 impl From<Opt> for Config {
 	fn from(opt: Opt) -> Self {
+		let (proxy_username, proxy_password) = match &opt.proxy_user {
+			Some(creds) => match creds.split_once(':') {
+				Some((user, password)) => (Some(user.to_string()), Some(password.to_string())),
+				None => (Some(creds.to_string()), None),
+			},
+			None => (None, None),
+		};
+
 		Self {
 			location: opt.location,
 			connect_timeout: opt.connect_timeout.map(Duration::from_millis),
@@ -97,10 +164,45 @@ impl From<Opt> for Config {
 			url: opt.url,
 			verbose: opt.verbose,
 			max_response_size: opt.max_response_size,
+			count: opt.count,
+			concurrency: opt.concurrency,
+			accept_encoding: if opt.compressed {
+				Some(vec!["gzip".into(), "br".into(), "deflate".into()])
+			} else {
+				None
+			},
+			http_version: if opt.http3 {
+				Some(HttpVersion::Http3)
+			} else if opt.http2 {
+				Some(HttpVersion::Http2)
+			} else {
+				None
+			},
+			proxy: opt.proxy,
+			proxy_tunnel: opt.proxy_tunnel,
+			proxy_username,
+			proxy_password,
+			range: opt.range.map(|start| (start, None)),
+			timeout: opt.timeout.map(Duration::from_millis),
+			low_speed_limit: opt.low_speed_limit,
+			low_speed_time: opt.low_speed_time.map(Duration::from_secs),
 		}
 	}
 }
 
+fn format_bytes(bytes: usize) -> String {
+	const KIB: f64 = 1024.0;
+	const MIB: f64 = KIB * 1024.0;
+	let bytes = bytes as f64;
+	if bytes >= MIB {
+		format!("{:.1} MiB", bytes / MIB)
+	} else if bytes >= KIB {
+		format!("{:.0} KiB", bytes / KIB)
+	} else {
+		format!("{} B", bytes as usize)
+	}
+}
+
 type ColorFormatter = fn(String) -> String;
 
 macro_rules! make_color {
@@ -120,10 +222,84 @@ const YELLOW: ColorFormatter = make_color!(33);
 const CYAN: ColorFormatter = make_color!(36);
 const GRAY: ColorFormatter = make_color!(38);
 
+fn format_millis(duration: Duration) -> String {
+	format!("{:.0}ms", duration.as_millis())
+}
+
+fn print_aggregate(aggregate: &AggregateTiming) {
+	if aggregate.samples == 0 {
+		println!(
+			"\n{}",
+			GRAY(format!(
+				"{} samples, {} failed",
+				aggregate.samples, aggregate.failures
+			))
+		);
+		return;
+	}
+
+	let header = format!(
+		"{:<18}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}",
+		"Phase", "min", "mean", "median", "p95", "p99", "max", "stddev"
+	);
+	println!("\n{}", GRAY(header));
+
+	let row = |name: &str, stats: &PhaseStats| {
+		println!(
+			"{:<18}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}",
+			name,
+			format_millis(stats.min),
+			format_millis(stats.mean),
+			format_millis(stats.median),
+			format_millis(stats.p95),
+			format_millis(stats.p99),
+			format_millis(stats.max),
+			format_millis(stats.stddev),
+		);
+	};
+
+	row("DNS Lookup", &aggregate.dns_resolution);
+	row("TCP Connection", &aggregate.tcp_connection);
+	row("TLS Handshake", &aggregate.tls_connection);
+	row("Server Processing", &aggregate.server_processing);
+	row("Content Transfer", &aggregate.content_transfer);
+	row("Total", &aggregate.total);
+
+	println!(
+		"\n{}",
+		GRAY(format!(
+			"{} samples, {} failed",
+			aggregate.samples, aggregate.failures
+		))
+	);
+}
+
 fn execute() -> Result<()> {
 	let mut opt = Opt::from_args();
 	opt.data = get_upload_data(opt.data)?;
 
+	if opt.follow {
+		let interval = Duration::from_millis(opt.interval);
+		let config = Config::from(opt.clone());
+		block_on(httpstat_follow(&config, interval, |bytes, timing| {
+			let mut stdout = io::stdout();
+			stdout.write_all(bytes)?;
+			stdout.flush()?;
+			eprintln!(
+				"{}",
+				GRAY(format!("[poll] total:{:.0}ms", timing.total.as_millis()))
+			);
+			Ok(())
+		}))?;
+		return Ok(());
+	}
+
+	if opt.count > 1 {
+		let aggregate = block_on(httpstat_samples(&Config::from(opt.clone())))?;
+		print_aggregate(&aggregate);
+		return Ok(());
+	}
+
 	let result = block_on(httpstat(&Config::from(opt.clone())))?;
 
 	println!(
@@ -142,6 +318,11 @@ fn execute() -> Result<()> {
 		)),
 	);
 
+	println!(
+		"{}",
+		GRAY(format!("negotiated HTTP/{}", result.negotiated.http_version))
+	);
+
 	for header in result.headers.iter() {
 		println!(
 			"{}{}",
@@ -150,6 +331,22 @@ fn execute() -> Result<()> {
 		);
 	}
 
+	if let Some(encoding) = &result.encoding {
+		let ratio = if result.compressed_size > 0 {
+			result.decompressed_size as f64 / result.compressed_size as f64
+		} else {
+			1.0
+		};
+		println!(
+			"\n{} {} {} {} ({:.1}x)",
+			GREEN(encoding.to_owned()),
+			GRAY(format_bytes(result.compressed_size)),
+			GRAY("\u{2192}".into()),
+			CYAN(format_bytes(result.decompressed_size)),
+			ratio,
+		);
+	}
+
 	if opt.save_body {
 		let tmpfile_name = nanoid!(6, &nanoid::alphabet::SAFE); //=> "93ce_Ltuub"
 		let tmpfile_path = format!("{}/tmp{}", env::temp_dir().to_str().unwrap(), tmpfile_name);
@@ -165,7 +362,33 @@ fn execute() -> Result<()> {
 	let format_a = make_color_formatter!(CYAN, "{:^7}"); //make_a_formatter();
 	let format_b = make_color_formatter!(CYAN, "{:<7}"); //make_b_formatter();
 
-	let output = if opt.url.starts_with("https") {
+	let output = if opt.url.starts_with("https") && opt.proxy_tunnel {
+		// When tunnelling, curl gives no timestamp separating the CONNECT exchange
+		// from the origin TLS handshake, so the third segment reports them together
+		// rather than showing a misleading ~0ms TLS cell.
+		format!(
+			r#"
+  DNS Lookup   TCP Connection    Proxy + TLS    Server Processing   Content Transfer
+[   {a0000}  |     {a0001}    |    {a0002}    |      {a0003}      |      {a0004}     ]
+             |                |               |                   |                  |
+    namelookup:{b0000}        |               |                   |                  |
+                        connect:{b0001}       |                   |                  |
+                                    pretransfer:{b0002}           |                  |
+                                                      starttransfer:{b0003}          |
+                                                                                 total:{b0004}
+"#,
+			a0000 = format_a(result.timing.dns_resolution),
+			a0001 = format_a(result.timing.tcp_connection),
+			a0002 = format_a(result.timing.proxy_connect + result.timing.tls_connection),
+			a0003 = format_a(result.timing.server_processing),
+			a0004 = format_a(result.timing.content_transfer),
+			b0000 = format_b(result.timing.namelookup),
+			b0001 = format_b(result.timing.connect),
+			b0002 = format_b(result.timing.pretransfer),
+			b0003 = format_b(result.timing.starttransfer),
+			b0004 = format_b(result.timing.total)
+		)
+	} else if opt.url.starts_with("https") {
 		format!(
 			r#"
   DNS Lookup   TCP Connection   TLS Handshake   Server Processing   Content Transfer
@@ -211,6 +434,7 @@ fn execute() -> Result<()> {
 	};
 
 	println!("{}", output);
+
 	Ok(())
 }
 